@@ -1,5 +1,14 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Channel;
 
 fn find_compose_file() -> Result<PathBuf, String> {
     let mut current = std::env::current_dir().map_err(|e| format!("Unable to read current dir: {e}"))?;
@@ -29,14 +38,117 @@ fn format_output(stdout: &[u8], stderr: &[u8]) -> String {
     out.trim().to_string()
 }
 
-fn run_checked(mut cmd: Command) -> Result<String, String> {
-    let output = cmd.output().map_err(|e| format!("Command failed to start: {e}"))?;
-    let rendered = format_output(&output.stdout, &output.stderr);
+/// A structured failure surfaced to the frontend so the UI can distinguish
+/// "docker not installed" (`exit_code: None`) from "compose exited 1", and show the
+/// exact command that failed for reproducibility. Failures raised before any process
+/// is spawned (see `CommandError::message`) also have `exit_code: None`, with an
+/// empty `command`.
+#[derive(Clone, Debug, Serialize)]
+pub struct CommandError {
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandError {
+    /// A precondition failure that isn't tied to a spawned process.
+    fn message(msg: impl Into<String>) -> Self {
+        CommandError {
+            command: String::new(),
+            exit_code: None,
+            stdout: String::new(),
+            stderr: msg.into(),
+        }
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(msg: String) -> Self {
+        CommandError::message(msg)
+    }
+}
+
+/// One entry in the command audit log.
+#[derive(Clone, Debug, Serialize)]
+pub struct CommandRecord {
+    /// The full rendered invocation (program, args, relevant env, working dir).
+    pub command: String,
+    /// Process exit code, or `None` if the command failed to launch.
+    pub exit_code: Option<i32>,
+    /// Unix epoch milliseconds at which the command completed.
+    pub ts: u64,
+}
+
+/// How many invocations the audit ring buffer retains.
+const HISTORY_CAPACITY: usize = 256;
+
+/// In-memory ring buffer of command invocations, exposed via `command_history`.
+fn command_log() -> &'static Mutex<VecDeque<CommandRecord>> {
+    static LOG: OnceLock<Mutex<VecDeque<CommandRecord>>> = OnceLock::new();
+    LOG.get_or_init(|| Mutex::new(VecDeque::with_capacity(HISTORY_CAPACITY)))
+}
+
+fn record_invocation(command: String, exit_code: Option<i32>) {
+    let mut log = command_log().lock().unwrap();
+    if log.len() == HISTORY_CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(CommandRecord {
+        command,
+        exit_code,
+        ts: now_millis(),
+    });
+}
+
+/// Render a command into a loggable, reproducible string: working dir, relevant env
+/// (`DOCKER_HOST`), program and args.
+fn render_command(cmd: &Command) -> String {
+    let mut parts = Vec::new();
+    if let Some(dir) = cmd.get_current_dir() {
+        parts.push(format!("(cd {})", dir.display()));
+    }
+    for (key, value) in cmd.get_envs() {
+        if key == "DOCKER_HOST" {
+            if let Some(value) = value {
+                parts.push(format!("DOCKER_HOST={}", value.to_string_lossy()));
+            }
+        }
+    }
+    parts.push(cmd.get_program().to_string_lossy().to_string());
+    for arg in cmd.get_args() {
+        parts.push(arg.to_string_lossy().to_string());
+    }
+    parts.join(" ")
+}
+
+/// Central execution layer every blocking command goes through: render the
+/// invocation, run it, append it to the audit log, and surface a structured error
+/// on failure.
+fn auto_run(mut cmd: Command) -> Result<String, CommandError> {
+    let command = render_command(&cmd);
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(e) => {
+            record_invocation(command.clone(), None);
+            return Err(CommandError {
+                command,
+                exit_code: None,
+                stdout: String::new(),
+                stderr: format!("Command failed to start: {e}"),
+            });
+        }
+    };
+    record_invocation(command.clone(), output.status.code());
     if output.status.success() {
-        return Ok(rendered);
+        return Ok(format_output(&output.stdout, &output.stderr));
     }
-    let code = output.status.code().map(|c| c.to_string()).unwrap_or_else(|| "unknown".to_string());
-    Err(format!("Command failed (exit {code})\n{rendered}"))
+    Err(CommandError {
+        command,
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
 }
 
 fn docker_compose_unsupported(rendered: &str) -> bool {
@@ -47,33 +159,58 @@ fn docker_compose_unsupported(rendered: &str) -> bool {
         || s.contains("unknown flag: --no-build")
 }
 
-fn run_compose(args: &[&str]) -> Result<String, String> {
+/// A container engine to target. Populating `docker_host` sets `DOCKER_HOST`, and
+/// `context` selects a named Docker context with `--context`, so the app can drive a
+/// compose stack on a remote machine the way `cross` does. Both are optional and an
+/// empty target runs against the local engine.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineTarget {
+    pub docker_host: Option<String>,
+    pub context: Option<String>,
+}
+
+/// Apply an engine target to a command: set `DOCKER_HOST` and, when the binary
+/// supports it, select a named context. The legacy `docker-compose` binary has no
+/// `--context` flag, so `supports_context` gates it.
+fn apply_target(cmd: &mut Command, target: &EngineTarget, supports_context: bool) {
+    if let Some(host) = &target.docker_host {
+        cmd.env("DOCKER_HOST", host);
+    }
+    if supports_context {
+        if let Some(context) = &target.context {
+            cmd.arg("--context").arg(context);
+        }
+    }
+}
+
+fn run_compose(target: &EngineTarget, args: &[&str]) -> Result<String, CommandError> {
     let compose_path = find_compose_file()?;
     let compose_dir = compose_path
         .parent()
         .ok_or_else(|| "Invalid compose file path".to_string())?;
 
     // Prefer `docker compose`, but fall back to the legacy `docker-compose` binary when necessary.
-    let docker_result = run_checked({
+    let docker_result = auto_run({
         let mut cmd = Command::new("docker");
-        cmd.current_dir(compose_dir)
-            .arg("compose")
-            .arg("-f")
-            .arg(&compose_path)
-            .args(args);
+        cmd.current_dir(compose_dir);
+        apply_target(&mut cmd, target, true);
+        cmd.arg("compose").arg("-f").arg(&compose_path).args(args);
         cmd
     });
 
     match docker_result {
         Ok(out) => Ok(out),
         Err(err) => {
-            // If docker isn't installed, `run_checked` would have failed to start; in that case
+            // If docker isn't installed, `auto_run` reports no exit code; in that case
             // try `docker-compose` before returning the error.
-            let is_not_found = err.to_lowercase().contains("failed to start");
-            if is_not_found || docker_compose_unsupported(&err) {
-                run_checked({
+            let is_not_found = err.exit_code.is_none();
+            if is_not_found || docker_compose_unsupported(&err.stderr) {
+                auto_run({
                     let mut cmd = Command::new("docker-compose");
-                    cmd.current_dir(compose_dir).arg("-f").arg(&compose_path).args(args);
+                    cmd.current_dir(compose_dir);
+                    apply_target(&mut cmd, target, false);
+                    cmd.arg("-f").arg(&compose_path).args(args);
                     cmd
                 })
             } else {
@@ -83,11 +220,321 @@ fn run_compose(args: &[&str]) -> Result<String, String> {
     }
 }
 
+/// Run a bare `docker ...` invocation against the given engine target, reusing the
+/// same `DOCKER_HOST`/`--context` wiring and `auto_run` error handling as
+/// `run_compose`. Used by the volume-management commands.
+fn run_docker(target: &EngineTarget, args: &[&str]) -> Result<String, CommandError> {
+    let mut cmd = Command::new("docker");
+    apply_target(&mut cmd, target, true);
+    cmd.args(args);
+    auto_run(cmd)
+}
+
+/// The compose project name, which Compose derives from and normalises to the
+/// lowercased name of the directory containing the compose file. Used to scope
+/// volume operations to this project.
+fn compose_project_name() -> Result<String, String> {
+    let compose_path = find_compose_file()?;
+    let dir = compose_path
+        .parent()
+        .ok_or_else(|| "Invalid compose file path".to_string())?;
+    let name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| "Could not determine compose project name".to_string())?;
+    Ok(name.to_lowercase())
+}
+
+/// The services defined in the compose project, from `compose config --services`.
+fn compose_services(target: &EngineTarget) -> Result<Vec<String>, CommandError> {
+    let raw = run_compose(target, &["config", "--services"])?;
+    Ok(raw
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Validate requested service names against the project's defined services so the
+/// UI gets a clear error rather than a cryptic Compose failure for an unknown name.
+fn validate_services(target: &EngineTarget, requested: &[String]) -> Result<(), CommandError> {
+    if requested.is_empty() {
+        return Ok(());
+    }
+    let known = compose_services(target)?;
+    let unknown: Vec<&str> = requested
+        .iter()
+        .filter(|name| !known.iter().any(|k| k == *name))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(CommandError::message(format!(
+            "Unknown service(s): {}. Known services: {}.",
+            unknown.join(", "),
+            known.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// A single container in the compose project, as reported by `docker compose ps`.
+#[derive(Debug, Serialize)]
+pub struct Container {
+    pub service: String,
+    pub name: String,
+    pub image: String,
+    pub state: String,
+    pub health: Option<String>,
+    pub ports: Option<String>,
+}
+
+/// Raw shape emitted by `docker compose ps --format json`. Docker uses PascalCase
+/// keys and omits or empties fields that don't apply, so everything defaults.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ComposePsRow {
+    #[serde(default)]
+    service: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    health: String,
+    #[serde(default)]
+    ports: String,
+}
+
+impl From<ComposePsRow> for Container {
+    fn from(row: ComposePsRow) -> Self {
+        let blank_to_none = |s: String| if s.trim().is_empty() { None } else { Some(s) };
+        Container {
+            service: row.service,
+            name: row.name,
+            image: row.image,
+            state: row.state,
+            health: blank_to_none(row.health),
+            ports: blank_to_none(row.ports),
+        }
+    }
+}
+
+/// Parse the stdout of `docker compose ps --format json`. Newer Compose prints one
+/// JSON object per line (NDJSON); older versions print a single JSON array. We try
+/// the array shape first and fall back to line-by-line parsing.
+fn parse_compose_ps(raw: &str) -> Result<Vec<Container>, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<ComposePsRow> = match serde_json::from_str::<Vec<ComposePsRow>>(trimmed) {
+        Ok(rows) => rows,
+        Err(_) => {
+            let mut rows = Vec::new();
+            for line in trimmed.lines() {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let row = serde_json::from_str(line)
+                    .map_err(|e| format!("Failed to parse compose ps output: {e}"))?;
+                rows.push(row);
+            }
+            rows
+        }
+    };
+
+    Ok(rows.into_iter().map(Container::from).collect())
+}
+
+/// A single line of output from a streaming compose invocation.
+#[derive(Clone, Debug, Serialize)]
+struct LogLine {
+    /// Which pipe the line came from: `"stdout"` or `"stderr"`.
+    stream: String,
+    text: String,
+    /// Unix epoch milliseconds at which the line was read.
+    ts: u64,
+}
+
+/// Registry of children spawned by the streaming commands, keyed by stream id so
+/// `compose_cancel` can kill a running process. Entries are removed once the
+/// process exits and its reader threads have joined.
+fn running_children() -> &'static Mutex<HashMap<u64, Arc<Mutex<Child>>>> {
+    static CHILDREN: OnceLock<Mutex<HashMap<u64, Arc<Mutex<Child>>>>> = OnceLock::new();
+    CHILDREN.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn next_stream_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(1);
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Spawn a compose invocation with piped stdout/stderr, mirroring `run_compose`'s
+/// preference for `docker compose` with a fallback to the legacy `docker-compose`
+/// binary when docker isn't present.
+fn spawn_compose(target: &EngineTarget, args: &[&str]) -> Result<Child, String> {
+    let compose_path = find_compose_file()?;
+    let compose_dir = compose_path
+        .parent()
+        .ok_or_else(|| "Invalid compose file path".to_string())?;
+
+    let mut docker = Command::new("docker");
+    docker.current_dir(compose_dir);
+    apply_target(&mut docker, target, true);
+    docker
+        .arg("compose")
+        .arg("-f")
+        .arg(&compose_path)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    match docker.spawn() {
+        Ok(child) => Ok(child),
+        Err(_) => {
+            let mut legacy = Command::new("docker-compose");
+            legacy.current_dir(compose_dir);
+            apply_target(&mut legacy, target, false);
+            legacy
+                .arg("-f")
+                .arg(&compose_path)
+                .args(args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| format!("Command failed to start: {e}"))
+        }
+    }
+}
+
+/// Read a pipe line by line, pushing each line through the channel until EOF.
+fn spawn_reader(
+    reader: impl Read + Send + 'static,
+    stream: &'static str,
+    channel: Channel<LogLine>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buffered = BufReader::new(reader);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match buffered.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let text = String::from_utf8_lossy(&buf)
+                .trim_end_matches(&['\n', '\r'][..])
+                .to_string();
+            let _ = channel.send(LogLine {
+                stream: stream.to_string(),
+                text,
+                ts: now_millis(),
+            });
+        }
+    })
+}
+
+/// Register `child`, stream both pipes through `channel`, and return the stream id.
+/// A supervisor thread joins the reader threads, reaps the process, and deregisters
+/// the child once it exits so cancelling or finishing never leaves orphaned threads.
+fn start_stream(mut child: Child, channel: Channel<LogLine>) -> u64 {
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    let mut readers = Vec::new();
+    if let Some(out) = stdout {
+        readers.push(spawn_reader(out, "stdout", channel.clone()));
+    }
+    if let Some(err) = stderr {
+        readers.push(spawn_reader(err, "stderr", channel.clone()));
+    }
+
+    let id = next_stream_id();
+    let child = Arc::new(Mutex::new(child));
+    running_children().lock().unwrap().insert(id, Arc::clone(&child));
+
+    thread::spawn(move || {
+        for reader in readers {
+            let _ = reader.join();
+        }
+        if let Ok(mut guard) = child.lock() {
+            let _ = guard.wait();
+        }
+        running_children().lock().unwrap().remove(&id);
+    });
+
+    id
+}
+
+#[tauri::command]
+fn compose_up_stream(
+    build: Option<bool>,
+    target: Option<EngineTarget>,
+    on_event: Channel<LogLine>,
+) -> Result<u64, String> {
+    let mut args = vec!["up", "-d", "--remove-orphans"];
+    if build.unwrap_or(true) {
+        args.push("--build");
+    } else {
+        args.push("--no-build");
+    }
+    let child = spawn_compose(&target.unwrap_or_default(), &args)?;
+    Ok(start_stream(child, on_event))
+}
+
 #[tauri::command]
-fn docker_version() -> Result<String, String> {
+fn compose_logs_stream(
+    service: Option<String>,
+    follow: bool,
+    target: Option<EngineTarget>,
+    on_event: Channel<LogLine>,
+) -> Result<u64, String> {
+    let mut args = vec!["logs"];
+    if follow {
+        args.push("--follow");
+    }
+    if let Some(ref svc) = service {
+        args.push(svc);
+    }
+    let child = spawn_compose(&target.unwrap_or_default(), &args)?;
+    Ok(start_stream(child, on_event))
+}
+
+#[tauri::command]
+fn compose_cancel(id: u64) -> Result<(), String> {
+    let child = running_children().lock().unwrap().get(&id).cloned();
+    match child {
+        Some(child) => child
+            .lock()
+            .unwrap()
+            .kill()
+            .map_err(|e| format!("Failed to cancel stream {id}: {e}")),
+        None => Err(format!("No running stream with id {id}")),
+    }
+}
+
+#[tauri::command]
+fn docker_version() -> Result<String, CommandError> {
     let mut cmd = Command::new("docker");
     cmd.arg("--version");
-    run_checked(cmd)
+    auto_run(cmd)
+}
+
+#[tauri::command]
+fn command_history() -> Vec<CommandRecord> {
+    command_log().lock().unwrap().iter().cloned().collect()
 }
 
 #[tauri::command]
@@ -96,24 +543,107 @@ fn compose_file_path() -> Result<String, String> {
 }
 
 #[tauri::command]
-fn compose_ps() -> Result<String, String> {
-    run_compose(&["ps"])
+fn compose_ps(target: Option<EngineTarget>) -> Result<String, CommandError> {
+    run_compose(&target.unwrap_or_default(), &["ps"])
 }
 
 #[tauri::command]
-fn compose_up(build: Option<bool>) -> Result<String, String> {
+fn compose_ps_json(target: Option<EngineTarget>) -> Result<Vec<Container>, CommandError> {
+    let raw = run_compose(&target.unwrap_or_default(), &["ps", "--format", "json"])?;
+    Ok(parse_compose_ps(&raw)?)
+}
+
+#[tauri::command]
+fn compose_up(build: Option<bool>, target: Option<EngineTarget>) -> Result<String, CommandError> {
     let mut args = vec!["up", "-d", "--remove-orphans"];
     if build.unwrap_or(true) {
         args.push("--build");
     } else {
         args.push("--no-build");
     }
-    run_compose(&args)
+    run_compose(&target.unwrap_or_default(), &args)
+}
+
+#[tauri::command]
+fn compose_down(target: Option<EngineTarget>) -> Result<String, CommandError> {
+    run_compose(&target.unwrap_or_default(), &["down"])
+}
+
+#[tauri::command]
+fn compose_restart(
+    service: Option<String>,
+    target: Option<EngineTarget>,
+) -> Result<String, CommandError> {
+    let target = target.unwrap_or_default();
+    let services: Vec<String> = service.into_iter().collect();
+    validate_services(&target, &services)?;
+    let mut args = vec!["restart"];
+    args.extend(services.iter().map(String::as_str));
+    run_compose(&target, &args)
 }
 
 #[tauri::command]
-fn compose_down() -> Result<String, String> {
-    run_compose(&["down"])
+fn compose_stop(services: Vec<String>, target: Option<EngineTarget>) -> Result<String, CommandError> {
+    let target = target.unwrap_or_default();
+    validate_services(&target, &services)?;
+    let mut args = vec!["stop"];
+    args.extend(services.iter().map(String::as_str));
+    run_compose(&target, &args)
+}
+
+#[tauri::command]
+fn compose_start(services: Vec<String>, target: Option<EngineTarget>) -> Result<String, CommandError> {
+    let target = target.unwrap_or_default();
+    validate_services(&target, &services)?;
+    let mut args = vec!["start"];
+    args.extend(services.iter().map(String::as_str));
+    run_compose(&target, &args)
+}
+
+#[tauri::command]
+fn compose_build(
+    service: Option<String>,
+    no_cache: bool,
+    target: Option<EngineTarget>,
+) -> Result<String, CommandError> {
+    let target = target.unwrap_or_default();
+    let services: Vec<String> = service.into_iter().collect();
+    validate_services(&target, &services)?;
+    let mut args = vec!["build"];
+    if no_cache {
+        args.push("--no-cache");
+    }
+    args.extend(services.iter().map(String::as_str));
+    run_compose(&target, &args)
+}
+
+#[tauri::command]
+fn list_volumes(target: Option<EngineTarget>) -> Result<String, CommandError> {
+    run_docker(&target.unwrap_or_default(), &["volume", "ls"])
+}
+
+#[tauri::command]
+fn prune_volumes(target: Option<EngineTarget>) -> Result<String, CommandError> {
+    run_docker(&target.unwrap_or_default(), &["volume", "prune", "-f"])
+}
+
+#[tauri::command]
+fn remove_project_volumes(target: Option<EngineTarget>) -> Result<String, CommandError> {
+    let target = target.unwrap_or_default();
+    let project = compose_project_name()?;
+    let filter = format!("label=com.docker.compose.project={project}");
+    let listed = run_docker(&target, &["volume", "ls", "--filter", &filter, "-q"])?;
+    let volumes: Vec<&str> = listed
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if volumes.is_empty() {
+        return Ok(format!("No volumes found for project '{project}'."));
+    }
+    let mut args = vec!["volume", "rm"];
+    args.extend(volumes);
+    run_docker(&target, &args)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -129,10 +659,22 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             docker_version,
+            command_history,
             compose_file_path,
             compose_ps,
+            compose_ps_json,
             compose_up,
-            compose_down
+            compose_up_stream,
+            compose_logs_stream,
+            compose_cancel,
+            compose_down,
+            compose_restart,
+            compose_stop,
+            compose_start,
+            compose_build,
+            list_volumes,
+            prune_volumes,
+            remove_project_volumes
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");